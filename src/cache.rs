@@ -0,0 +1,143 @@
+//! Content-addressed cache for profiling reports, modeled on sccache's
+//! compiler-output cache: a digest over everything that could change the
+//! result is used as the cache key, so invalidation falls out for free.
+
+use crate::python::Interpreter;
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default cache location, `$XDG_CACHE_HOME/exetrix` or `~/.cache/exetrix`.
+pub fn default_cache_dir() -> PathBuf {
+    let base = std::env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|_| std::env::var("HOME").map(|h| PathBuf::from(h).join(".cache")))
+        .unwrap_or_else(|_| PathBuf::from(".cache"));
+    base.join("exetrix")
+}
+
+/// Everything that determines whether a previous run can be reused.
+pub struct CacheKeyInput<'a> {
+    pub script_path: &'a Path,
+    pub forwarded_args: &'a [String],
+    pub python: &'a Interpreter,
+    pub format: &'a str,
+    pub ignore: &'a [String],
+}
+
+/// SHA-256 over the script bytes, each forwarded arg (NUL-separated), the
+/// requested report format, the ignore-pattern list, the `python --version`
+/// output, and the bundled profiler source. Any change to these inputs
+/// changes the digest, so no separate expiry is needed.
+pub fn compute_digest(input: &CacheKeyInput) -> Result<String> {
+    let script_bytes = fs::read(input.script_path)
+        .with_context(|| format!("reading {}", input.script_path.display()))?;
+
+    let version_output = input
+        .python
+        .command()
+        .arg("--version")
+        .output()
+        .with_context(|| format!("running `{} --version`", input.python.display()))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&script_bytes);
+    for arg in input.forwarded_args {
+        hasher.update(arg.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher.update(input.format.as_bytes());
+    for pattern in input.ignore {
+        hasher.update(pattern.as_bytes());
+        hasher.update([0u8]);
+    }
+    hasher.update(&version_output.stdout);
+    hasher.update(&version_output.stderr);
+    hasher.update(crate::PROFILER_PY.as_bytes());
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub enum CacheOutcome {
+    Hit,
+    Miss,
+}
+
+/// Looks up `digest` under `cache_dir`, copying whichever of the
+/// requested formats (`wants_json`/`wants_html`) are cached into
+/// `report_dir` on a hit. Missing any requested format counts as a miss.
+/// A hit refreshes the entry's timestamps so eviction sees it as recently
+/// used.
+pub fn lookup(
+    cache_dir: &Path,
+    digest: &str,
+    report_dir: &Path,
+    wants_json: bool,
+    wants_html: bool,
+) -> Result<CacheOutcome> {
+    let entry_dir = cache_dir.join(digest);
+    let json = entry_dir.join("report.json");
+    let html = entry_dir.join("report.html");
+    if (wants_json && !json.exists()) || (wants_html && !html.exists()) {
+        return Ok(CacheOutcome::Miss);
+    }
+
+    fs::create_dir_all(report_dir)?;
+    if wants_json {
+        fs::copy(&json, report_dir.join("report.json"))?;
+    }
+    if wants_html {
+        fs::copy(&html, report_dir.join("report.html"))?;
+    }
+    let now = std::time::SystemTime::now();
+    if let Ok(dir) = fs::File::open(&entry_dir) {
+        let _ = dir.set_times(fs::FileTimes::new().set_accessed(now).set_modified(now));
+    }
+    Ok(CacheOutcome::Hit)
+}
+
+const MAX_CACHE_ENTRIES: usize = 64;
+
+/// Stores whichever of the requested formats were just generated under
+/// `digest`, then evicts the least-recently-used entries if the cache has
+/// grown past its size cap.
+pub fn store(
+    cache_dir: &Path,
+    digest: &str,
+    report_dir: &Path,
+    wants_json: bool,
+    wants_html: bool,
+) -> Result<()> {
+    let entry_dir = cache_dir.join(digest);
+    fs::create_dir_all(&entry_dir)?;
+    if wants_json {
+        fs::copy(report_dir.join("report.json"), entry_dir.join("report.json"))?;
+    }
+    if wants_html {
+        fs::copy(report_dir.join("report.html"), entry_dir.join("report.html"))?;
+    }
+    evict_lru(cache_dir)?;
+    Ok(())
+}
+
+fn evict_lru(cache_dir: &Path) -> Result<()> {
+    let mut entries: Vec<_> = match fs::read_dir(cache_dir) {
+        Ok(rd) => rd.filter_map(|e| e.ok()).collect(),
+        Err(_) => return Ok(()),
+    };
+    if entries.len() <= MAX_CACHE_ENTRIES {
+        return Ok(());
+    }
+
+    entries.sort_by_key(|e| {
+        e.metadata()
+            .and_then(|m| m.accessed().or_else(|_| m.modified()))
+            .ok()
+    });
+    let excess = entries.len() - MAX_CACHE_ENTRIES;
+    for entry in entries.into_iter().take(excess) {
+        let _ = fs::remove_dir_all(entry.path());
+    }
+    Ok(())
+}
@@ -0,0 +1,160 @@
+//! `compare` subcommand: diffs two profiling reports and flags regressions
+//! beyond a configurable threshold, treating the current run as the
+//! "actual" result and the baseline as "expected" (compiletest's
+//! compare-mode naming), so CI can gate merges on it.
+
+use anyhow::{bail, Context, Result};
+use clap::Args;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Debug)]
+pub struct CompareArgs {
+    /// Previously saved report.json to treat as the expected baseline
+    #[arg(long, value_name = "FILE")]
+    baseline: PathBuf,
+
+    /// report.json from the current run, treated as the actual result
+    #[arg(long, value_name = "FILE")]
+    current: PathBuf,
+
+    /// Percentage change in cumulative time, call count, or exclusive time
+    /// that counts as a regression, e.g. `10%` or `10`
+    #[arg(long, default_value = "10%", value_parser = parse_threshold)]
+    threshold: f64,
+
+    /// Directory to write compare.json and compare.html into
+    #[arg(long, value_name = "DIR", default_value = "report")]
+    output_dir: PathBuf,
+}
+
+fn parse_threshold(s: &str) -> Result<f64, String> {
+    let trimmed = s.trim().trim_end_matches('%');
+    trimmed
+        .parse::<f64>()
+        .map_err(|e| format!("invalid threshold `{s}`: {e}"))
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct FunctionDiff {
+    name: String,
+    metric: &'static str,
+    baseline: f64,
+    current: f64,
+    pct_change: f64,
+}
+
+const METRICS: &[&str] = &["cumulative_time", "call_count", "total_time"];
+
+/// Runs the comparison, writing `compare.json`/`compare.html` into
+/// `args.output_dir`. Returns `true` if nothing regressed past the
+/// threshold, so the caller can map that to a process exit code.
+pub fn run(args: CompareArgs) -> Result<bool> {
+    let baseline = load_report(&args.baseline)?;
+    let current = load_report(&args.current)?;
+    let diffs = diff_reports(&baseline, &current, args.threshold);
+
+    fs::create_dir_all(&args.output_dir)?;
+    let json_path = args.output_dir.join("compare.json");
+    fs::write(&json_path, serde_json::to_string_pretty(&diffs)?)?;
+
+    let html_path = args.output_dir.join("compare.html");
+    fs::write(&html_path, render_html(&diffs, args.threshold))?;
+
+    println!(
+        "Compared {} against {}",
+        args.current.display(),
+        args.baseline.display()
+    );
+    println!("   - JSON: {}", json_path.display());
+    println!("   - HTML: {}", html_path.display());
+    println!();
+
+    if diffs.is_empty() {
+        println!("No regressions beyond {:.1}% threshold", args.threshold);
+    } else {
+        println!(
+            "{} regression(s) beyond {:.1}% threshold:",
+            diffs.len(),
+            args.threshold
+        );
+        for d in &diffs {
+            println!(
+                "   - {} [{}]: {:.2} -> {:.2} ({:+.1}%)",
+                d.name, d.metric, d.baseline, d.current, d.pct_change
+            );
+        }
+    }
+
+    Ok(diffs.is_empty())
+}
+
+fn load_report(path: &Path) -> Result<BTreeMap<String, Value>> {
+    let text = fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let value: Value =
+        serde_json::from_str(&text).with_context(|| format!("parsing {}", path.display()))?;
+    let Value::Object(functions) = value else {
+        bail!("{} is not a JSON object of per-function stats", path.display());
+    };
+    Ok(functions.into_iter().collect())
+}
+
+/// Compares every metric the current run shares with the baseline for
+/// each function present in both, sorted by largest regression first.
+fn diff_reports(
+    baseline: &BTreeMap<String, Value>,
+    current: &BTreeMap<String, Value>,
+    threshold: f64,
+) -> Vec<FunctionDiff> {
+    let mut diffs = Vec::new();
+
+    for (name, current_stats) in current {
+        let Some(baseline_stats) = baseline.get(name) else {
+            continue;
+        };
+        for metric in METRICS {
+            let before = baseline_stats.get(*metric).and_then(Value::as_f64);
+            let after = current_stats.get(*metric).and_then(Value::as_f64);
+            let (Some(before), Some(after)) = (before, after) else {
+                continue;
+            };
+            if before == 0.0 {
+                continue;
+            }
+
+            let pct_change = (after - before) / before * 100.0;
+            if pct_change > threshold {
+                diffs.push(FunctionDiff {
+                    name: name.clone(),
+                    metric,
+                    baseline: before,
+                    current: after,
+                    pct_change,
+                });
+            }
+        }
+    }
+
+    diffs.sort_by(|a, b| b.pct_change.partial_cmp(&a.pct_change).unwrap());
+    diffs
+}
+
+fn render_html(diffs: &[FunctionDiff], threshold: f64) -> String {
+    let mut rows = String::new();
+    for d in diffs {
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{:+.1}%</td></tr>\n",
+            d.name, d.metric, d.baseline, d.current, d.pct_change
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html><head><title>Exetrix regression report</title></head>\n\
+         <body>\n<h1>Regressions beyond {threshold:.1}%</h1>\n\
+         <table border=\"1\">\n<tr><th>Function</th><th>Metric</th><th>Baseline</th><th>Current</th><th>Change</th></tr>\n\
+         {rows}</table>\n</body></html>\n"
+    )
+}
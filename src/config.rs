@@ -0,0 +1,46 @@
+//! Ancestor-directory config discovery, following the same walk-up-to-
+//! find-a-project-file pattern as the `x` tool's search for `x.py`: look
+//! in the current directory and each parent for `exetrix.toml` or
+//! `.exetrix.toml` so a project can fix its profiling conventions once at
+//! the repo root.
+//!
+//! Precedence for any given setting is: CLI flag > environment variable >
+//! nearest config file > built-in default.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::env;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    pub output_dir: Option<PathBuf>,
+    pub format: Option<String>,
+    pub python: Option<String>,
+    pub cache_dir: Option<PathBuf>,
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+const CONFIG_NAMES: &[&str] = &["exetrix.toml", ".exetrix.toml"];
+
+/// Walks from the current directory up through its ancestors looking for
+/// the nearest config file, parsing and returning the first one found.
+pub fn discover() -> Result<Option<Config>> {
+    let start = env::current_dir()?;
+    for dir in start.ancestors() {
+        for name in CONFIG_NAMES {
+            let candidate = dir.join(name);
+            if candidate.is_file() {
+                return load(&candidate).map(Some);
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn load(path: &Path) -> Result<Config> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    toml::from_str(&text).with_context(|| format!("parsing {}", path.display()))
+}
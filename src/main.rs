@@ -1,60 +1,216 @@
+mod cache;
+mod compare;
+mod config;
+mod python;
+mod wrapper;
+
+use anyhow::{bail, Result};
+use cache::CacheOutcome;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
 use std::env;
 use std::fs;
-use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use tempfile::tempdir;
 
 const PROFILER_PY: &str = include_str!("profiler_wrapper.py");
 
-fn main() -> anyhow::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        eprintln!("Usage: {} <python_script> [args...]", args[0]);
-        eprintln!("       Creates a performance report for the Python script");
-        eprintln!();
-        eprintln!("Example: {} my_script.py --input data.csv", args[0]);
-        std::process::exit(2);
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum ReportFormat {
+    Json,
+    Html,
+    Both,
+}
+
+impl ReportFormat {
+    fn wants_json(self) -> bool {
+        matches!(self, ReportFormat::Json | ReportFormat::Both)
+    }
+
+    fn wants_html(self) -> bool {
+        matches!(self, ReportFormat::Html | ReportFormat::Both)
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Html => "html",
+            ReportFormat::Both => "both",
+        }
+    }
+}
+
+/// Profiles a Python script and generates a performance report.
+#[derive(Parser, Debug)]
+#[command(name = "exetrix", version, about)]
+#[command(group(ArgGroup::new("verbosity").args(["quiet", "verbose"])))]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Subcmd>,
+
+    /// Directory to write the report into (default: ../../report next to the binary)
+    #[arg(long, value_name = "DIR")]
+    output_dir: Option<PathBuf>,
+
+    /// Which report format(s) to generate (default: resolved from env/config, else both)
+    #[arg(long, value_enum)]
+    format: Option<ReportFormat>,
+
+    /// Interpreter to run the script under (default: resolved automatically)
+    #[arg(long, value_name = "PATH")]
+    python: Option<String>,
+
+    /// Suppress non-essential output
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Print extra diagnostic output
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Open the HTML report in a browser once profiling completes
+    #[arg(long)]
+    open: bool,
+
+    /// Skip the profiling cache entirely
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Directory to use for the profiling cache
+    #[arg(long, value_name = "DIR")]
+    cache_dir: Option<PathBuf>,
+
+    /// Script to profile, and (after `--`) its own arguments
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    script_and_args: Vec<String>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Subcmd {
+    /// Compare two profiling reports and fail if a metric regressed past a threshold
+    Compare(compare::CompareArgs),
+}
+
+fn main() -> Result<()> {
+    if wrapper::is_wrapper_mode() {
+        let args: Vec<String> = env::args().collect();
+        let interpreter = python::resolve(env::var("EXETRIX_PYTHON").ok().as_deref())?;
+        let code = wrapper::run(&interpreter, &args[1..])?;
+        std::process::exit(code);
+    }
+
+    let cli = Cli::parse();
+
+    if let Some(Subcmd::Compare(args)) = cli.command {
+        let no_regressions = compare::run(args)?;
+        if !no_regressions {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if cli.script_and_args.is_empty() {
+        bail!("no script given; pass a script to profile, or use `exetrix compare`");
     }
 
+    let script = cli.script_and_args[0].clone();
+    let forwarded_args = cli.script_and_args[1..].to_vec();
+
+    let config = config::discover()?;
+
     let exe_path = env::current_exe()?;
     let exe_dir = exe_path.parent().unwrap();
-    
-    let report_dir = exe_dir.join("../../report");
+    let python_override = resolve_opt(
+        cli.python.clone(),
+        "EXETRIX_PYTHON",
+        config.as_ref().and_then(|c| c.python.clone()),
+    );
+    let python = python::resolve(python_override.as_deref())?;
+    let report_dir = resolve(
+        cli.output_dir.clone(),
+        "EXETRIX_OUTPUT_DIR",
+        config.as_ref().and_then(|c| c.output_dir.clone()),
+        || exe_dir.join("../../report"),
+    );
+    let cache_dir = resolve(
+        cli.cache_dir.clone(),
+        "EXETRIX_CACHE_DIR",
+        config.as_ref().and_then(|c| c.cache_dir.clone()),
+        cache::default_cache_dir,
+    );
+    let ignore = config.as_ref().map(|c| c.ignore.clone()).unwrap_or_default();
+    let format = resolve_format(cli.format, config.as_ref().and_then(|c| c.format.clone()))?;
+
+    let digest = if cli.no_cache {
+        None
+    } else {
+        let input = cache::CacheKeyInput {
+            script_path: script.as_ref(),
+            forwarded_args: &forwarded_args,
+            python: &python,
+            format: format.as_str(),
+            ignore: &ignore,
+        };
+        Some(cache::compute_digest(&input)?)
+    };
+
+    if let Some(digest) = &digest {
+        let hit = cache::lookup(
+            &cache_dir,
+            digest,
+            &report_dir,
+            format.wants_json(),
+            format.wants_html(),
+        )?;
+        if let CacheOutcome::Hit = hit {
+            if !cli.quiet {
+                println!("Cache hit ({digest}) - reusing previous report, skipping execution");
+                println!();
+            }
+            return finish(&report_dir, format, cli.open);
+        }
+        if cli.verbose {
+            println!("Cache miss ({digest}) - running profiler");
+        }
+    }
+
     if report_dir.exists() {
-        println!("Cleaning previous report directory...");
+        if !cli.quiet {
+            println!("Cleaning previous report directory...");
+        }
         fs::remove_dir_all(&report_dir)?;
     }
     fs::create_dir_all(&report_dir)?;
 
     let temp_dir = tempdir()?;
     let profiler_path = temp_dir.path().join("profiler_wrapper.py");
-    {
-        let mut f = fs::File::create(&profiler_path)?;
-        f.write_all(PROFILER_PY.as_bytes())?;
-    }
+    fs::write(&profiler_path, PROFILER_PY)?;
 
-    let mut cmd = Command::new("python");
-    cmd.arg(profiler_path.to_str().unwrap())
+    let mut cmd = python.command();
+    cmd.arg(&profiler_path)
         .arg("--report-dir")
-        .arg(report_dir.to_str().unwrap())
-        .arg("--")
-        .arg(&args[1]);
-    
-    for a in args.iter().skip(2) {
-        cmd.arg(a);
+        .arg(&report_dir)
+        .arg("--format")
+        .arg(format.as_str());
+    for pattern in &ignore {
+        cmd.arg("--ignore").arg(pattern);
     }
+    cmd.arg("--").arg(&script);
+    cmd.args(&forwarded_args);
 
     cmd.stdin(Stdio::inherit())
-       .stdout(Stdio::inherit())
-       .stderr(Stdio::inherit());
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
 
-    println!("Running Python script under profiler...");
-    println!("   Script: {}", args[1]);
-    if args.len() > 2 {
-        println!("   Args: {}", args[2..].join(" "));
+    if !cli.quiet {
+        println!("Running Python script under profiler...");
+        println!("   Script: {script}");
+        if !forwarded_args.is_empty() {
+            println!("   Args: {}", forwarded_args.join(" "));
+        }
+        println!();
     }
-    println!();
-    
+
     let status = cmd.status()?;
 
     if !status.success() {
@@ -62,12 +218,99 @@ fn main() -> anyhow::Result<()> {
         std::process::exit(status.code().unwrap_or(1));
     }
 
-    println!("\nProfiling complete!");
+    if let Some(digest) = &digest {
+        cache::store(
+            &cache_dir,
+            digest,
+            &report_dir,
+            format.wants_json(),
+            format.wants_html(),
+        )?;
+    }
+
+    if !cli.quiet {
+        println!("\nProfiling complete!");
+    }
+    finish(&report_dir, format, cli.open)
+}
+
+/// Resolves a setting using the documented precedence: CLI flag, then
+/// environment variable, then the nearest `exetrix.toml`, then a
+/// built-in default.
+fn resolve<T: From<String>>(
+    cli_value: Option<T>,
+    env_var: &str,
+    config_value: Option<T>,
+    default: impl FnOnce() -> T,
+) -> T {
+    cli_value
+        .or_else(|| env::var(env_var).ok().map(T::from))
+        .or(config_value)
+        .unwrap_or_else(default)
+}
+
+/// Like [`resolve`], but for settings with no built-in default (the
+/// interpreter, which falls back to its own probing logic instead).
+fn resolve_opt<T: From<String>>(
+    cli_value: Option<T>,
+    env_var: &str,
+    config_value: Option<T>,
+) -> Option<T> {
+    cli_value
+        .or_else(|| env::var(env_var).ok().map(T::from))
+        .or(config_value)
+}
+
+/// Resolves the report format with the same CLI > env > config > default
+/// precedence as [`resolve`], parsing `EXETRIX_FORMAT`/the config value
+/// against the same value names as `--format`, but case-insensitively.
+fn resolve_format(cli_value: Option<ReportFormat>, config_value: Option<String>) -> Result<ReportFormat> {
+    if let Some(format) = cli_value {
+        return Ok(format);
+    }
+    if let Ok(raw) = env::var("EXETRIX_FORMAT") {
+        return ReportFormat::from_str(&raw, true).map_err(anyhow::Error::msg);
+    }
+    if let Some(raw) = config_value {
+        return ReportFormat::from_str(&raw, true).map_err(anyhow::Error::msg);
+    }
+    Ok(ReportFormat::Both)
+}
+
+/// Prints the paths of whatever reports were generated and, if `--open`
+/// was passed, launches the HTML report in the default browser.
+fn finish(report_dir: &Path, format: ReportFormat, open: bool) -> Result<()> {
     println!("Reports generated:");
-    println!("   - JSON: {}", report_dir.join("report.json").display());
-    println!("   - HTML: {}", report_dir.join("report.html").display());
+    if format.wants_json() {
+        println!("   - JSON: {}", report_dir.join("report.json").display());
+    }
+    if format.wants_html() {
+        println!("   - HTML: {}", report_dir.join("report.html").display());
+    }
     println!();
-    println!("Open report.html in your browser to view the interactive report");
 
+    if open && format.wants_html() {
+        open_in_browser(&report_dir.join("report.html"))?;
+    } else if format.wants_html() {
+        println!("Open report.html in your browser to view the interactive report");
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn open_in_browser(path: &Path) -> Result<()> {
+    Command::new("open").arg(path).status()?;
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn open_in_browser(path: &Path) -> Result<()> {
+    Command::new("cmd").args(["/C", "start", ""]).arg(path).status()?;
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn open_in_browser(path: &Path) -> Result<()> {
+    Command::new("xdg-open").arg(path).status()?;
     Ok(())
 }
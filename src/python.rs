@@ -0,0 +1,156 @@
+//! Robust interpreter resolution, in the spirit of the
+//! `get_path_for_executable`-style lookup rust-analyzer's project model
+//! uses to find a real `rustc`: try the most specific signal first, then
+//! fall back through a list of candidates, verifying each one actually
+//! runs and reports Python 3 before trusting it.
+
+use anyhow::{bail, Result};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// An interpreter to invoke, split into a program and its leading args
+/// (e.g. `py -3` on Windows) since `Command::new` only takes one token.
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl Interpreter {
+    fn new(program: impl Into<String>, args: &[&str]) -> Self {
+        Interpreter {
+            program: program.into(),
+            args: args.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// A `Command` pre-loaded with this interpreter's leading args.
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+        cmd
+    }
+
+    /// A single string for display, e.g. in error messages.
+    pub fn display(&self) -> String {
+        if self.args.is_empty() {
+            self.program.clone()
+        } else {
+            format!("{} {}", self.program, self.args.join(" "))
+        }
+    }
+}
+
+/// Resolves which interpreter to run the target script under.
+///
+/// Order follows the same CLI > environment > config > default precedence
+/// documented in `config.rs`: `override_` (the `--python`/`EXETRIX_PYTHON`/
+/// config value, if any) wins first, then `$VIRTUAL_ENV/bin/python` (an
+/// active virtualenv, as the strongest ambient signal), then `python3`,
+/// `python`, and the Windows `py -3` launcher, in that order. Each
+/// candidate is verified by running `--version` and checking it reports
+/// Python 3, rather than trusted on name alone.
+pub fn resolve(override_: Option<&str>) -> Result<Interpreter> {
+    let mut tried = Vec::new();
+
+    if let Some(python) = override_ {
+        let candidate = Interpreter::new(python, &[]);
+        if is_python3(&candidate) {
+            return Ok(candidate);
+        }
+        tried.push(candidate.display());
+    }
+
+    if let Some(venv) = env::var_os("VIRTUAL_ENV") {
+        let candidate = Interpreter::new(venv_python(PathBuf::from(venv)), &[]);
+        if is_python3(&candidate) {
+            return Ok(candidate);
+        }
+        tried.push(candidate.display());
+    }
+
+    for candidate in default_candidates() {
+        // A bare name like "python" can resolve back to this very binary
+        // when exetrix itself is installed as the `python` shim on PATH
+        // (see wrapper mode) - skip it rather than recursing into ourselves.
+        if find_in_path(&candidate.program).is_some_and(|p| is_self(&p)) {
+            tried.push(format!("{} (resolved to this binary, skipped)", candidate.display()));
+            continue;
+        }
+        if is_python3(&candidate) {
+            return Ok(candidate);
+        }
+        tried.push(candidate.display());
+    }
+
+    bail!(
+        "could not find a Python 3 interpreter; tried: {}",
+        tried.join(", ")
+    );
+}
+
+#[cfg(windows)]
+fn venv_python(venv: PathBuf) -> String {
+    venv.join("Scripts")
+        .join("python.exe")
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(not(windows))]
+fn venv_python(venv: PathBuf) -> String {
+    venv.join("bin").join("python").to_string_lossy().into_owned()
+}
+
+#[cfg(windows)]
+fn default_candidates() -> Vec<Interpreter> {
+    vec![
+        Interpreter::new("python3", &[]),
+        Interpreter::new("python", &[]),
+        Interpreter::new("py", &["-3"]),
+    ]
+}
+
+#[cfg(not(windows))]
+fn default_candidates() -> Vec<Interpreter> {
+    vec![Interpreter::new("python3", &[]), Interpreter::new("python", &[])]
+}
+
+/// Searches `$PATH` for an executable named `name`, the same way the shell
+/// would resolve a bare command, so we can tell whether a candidate
+/// actually points at this running binary before trusting it.
+fn find_in_path(name: &str) -> Option<PathBuf> {
+    let path_var = env::var_os("PATH")?;
+    env::split_paths(&path_var).find_map(|dir| {
+        let candidate = dir.join(name);
+        #[cfg(windows)]
+        let candidate = candidate.with_extension("exe");
+        candidate.is_file().then_some(candidate)
+    })
+}
+
+/// Whether `path` is the currently-running exetrix executable.
+fn is_self(path: &Path) -> bool {
+    let (Ok(candidate), Ok(current)) = (path.canonicalize(), env::current_exe().and_then(|p| p.canonicalize())) else {
+        return false;
+    };
+    candidate == current
+}
+
+/// Runs `interpreter --version` and checks the output mentions Python 3.
+fn is_python3(interpreter: &Interpreter) -> bool {
+    let output = match interpreter.command().arg("--version").output() {
+        Ok(o) => o,
+        Err(_) => return false,
+    };
+    if !output.status.success() {
+        return false;
+    }
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    text.contains("Python 3")
+}
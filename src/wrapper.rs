@@ -0,0 +1,107 @@
+//! Transparent wrapper/shim mode, in the spirit of rust-analyzer's
+//! `RA_RUSTC_WRAPPER`: when `EXETRIX_WRAPPER=1` is set, this binary can sit
+//! in front of `python` on `PATH` (or as an interpreter override for tox /
+//! build systems) and profile every invocation handed to it, forwarding the
+//! real interpreter's exit code unchanged.
+
+use crate::python::Interpreter;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Whether the binary was invoked as a wrapper rather than directly.
+pub fn is_wrapper_mode() -> bool {
+    env::var("EXETRIX_WRAPPER").map(|v| v == "1").unwrap_or(false)
+}
+
+/// Interpreter flags that take a separate value argument (e.g. `-W error`),
+/// so the script-path scan below doesn't mistake the value for the script.
+const VALUE_FLAGS: &[&str] = &["-W", "-X", "-Q"];
+
+/// Finds the index of the script path in a `python`-style command line,
+/// skipping leading interpreter flags the way the real interpreter would.
+/// Returns `None` for `-m <module>` / `-c <code>` invocations, which have
+/// no script path to profile.
+fn find_script_index(args: &[String]) -> Option<usize> {
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if arg == "-m" || arg == "-c" {
+            return None;
+        }
+        if VALUE_FLAGS.contains(&arg) {
+            i += 2;
+            continue;
+        }
+        if arg.starts_with('-') {
+            i += 1;
+            continue;
+        }
+        return Some(i);
+    }
+    None
+}
+
+/// Runs in wrapper mode: `args` is the command line the wrapper was handed,
+/// without the program name (e.g. `["-m", "pytest", ...]` or `["-O", "script.py"]`).
+/// Anything that isn't a direct `.py` script invocation - `-m`/`-c`
+/// invocations, or a target that doesn't end in `.py` - is passed through
+/// untouched so the wrapper is a safe drop-in for every other invocation of
+/// the shimmed interpreter.
+pub fn run(python: &Interpreter, args: &[String]) -> anyhow::Result<i32> {
+    let script_index = find_script_index(args);
+    let Some(script_index) = script_index.filter(|&i| args[i].ends_with(".py")) else {
+        return passthrough(python, args);
+    };
+    let script = &args[script_index];
+
+    let report_dir = per_invocation_report_dir(Path::new(script));
+    std::fs::create_dir_all(&report_dir)?;
+
+    let temp_dir = tempfile::tempdir()?;
+    let profiler_path = temp_dir.path().join("profiler_wrapper.py");
+    std::fs::write(&profiler_path, crate::PROFILER_PY)?;
+
+    let mut cmd = python.command();
+    cmd.args(&args[..script_index])
+        .arg(&profiler_path)
+        .arg("--report-dir")
+        .arg(&report_dir)
+        .arg("--");
+    cmd.args(&args[script_index..]);
+
+    cmd.stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+
+    let status = cmd.status()?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Runs the real interpreter directly with no profiling, for command lines
+/// that don't target a `.py` file.
+fn passthrough(python: &Interpreter, args: &[String]) -> anyhow::Result<i32> {
+    let status = python
+        .command()
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// Derives a report directory from the script's basename plus a timestamp
+/// so concurrent spawns of the wrapper don't clobber each other's reports.
+fn per_invocation_report_dir(script: &Path) -> PathBuf {
+    let stem = script
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("script");
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    env::temp_dir().join("exetrix-reports").join(format!("{stem}-{timestamp}"))
+}